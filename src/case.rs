@@ -0,0 +1,110 @@
+//! Case-conversion helpers for the `rename_all` container attribute.
+//!
+//! Identifiers are split into lowercase words (splitting on `_`, `-`, and
+//! lower-to-upper transitions) and then rejoined in the requested style.
+
+/// Case styles accepted by `#[xml(rename_all = "...")]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Case {
+    Pascal,
+    Camel,
+    Snake,
+    Kebab,
+}
+
+impl Case {
+    /// Parses the string a user writes in `rename_all = "..."`.
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "PascalCase" => Some(Case::Pascal),
+            "camelCase" => Some(Case::Camel),
+            "snake_case" => Some(Case::Snake),
+            "kebab-case" => Some(Case::Kebab),
+            _ => None,
+        }
+    }
+}
+
+/// Converts `ident` (a Rust field/type name) into `case`.
+pub(crate) fn convert(ident: &str, case: Case) -> String {
+    let words = split_words(ident);
+    match case {
+        Case::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+        Case::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+            .collect(),
+        Case::Snake => words.join("_"),
+        Case::Kebab => words.join("-"),
+    }
+}
+
+/// Splits an identifier into lowercase words on `_`/`-` and lower->upper
+/// transitions, e.g. `"loopCount"` and `"loop_count"` both yield
+/// `["loop", "count"]`.
+fn split_words(ident: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+
+    for c in ident.chars() {
+        if c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(current.to_lowercase());
+                current = String::new();
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            words.push(current.to_lowercase());
+            current = String::new();
+        }
+        prev_lower = c.is_lowercase();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current.to_lowercase());
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_each_case() {
+        assert_eq!(convert("loop_count", Case::Pascal), "LoopCount");
+        assert_eq!(convert("loop_count", Case::Camel), "loopCount");
+        assert_eq!(convert("loopCount", Case::Snake), "loop_count");
+        assert_eq!(convert("loopCount", Case::Kebab), "loop-count");
+    }
+
+    #[test]
+    fn splits_words_on_underscore_hyphen_and_case_transitions() {
+        assert_eq!(split_words("loop_count"), vec!["loop", "count"]);
+        assert_eq!(split_words("loop-count"), vec!["loop", "count"]);
+        assert_eq!(split_words("loopCount"), vec!["loop", "count"]);
+        assert_eq!(split_words("LoopCount"), vec!["loop", "count"]);
+        assert_eq!(split_words("loop"), vec!["loop"]);
+    }
+
+    #[test]
+    fn from_str_accepts_known_styles_and_rejects_unknown() {
+        assert_eq!(Case::from_str("PascalCase"), Some(Case::Pascal));
+        assert_eq!(Case::from_str("camelCase"), Some(Case::Camel));
+        assert_eq!(Case::from_str("snake_case"), Some(Case::Snake));
+        assert_eq!(Case::from_str("kebab-case"), Some(Case::Kebab));
+        assert_eq!(Case::from_str("Title Case"), None);
+    }
+}
@@ -0,0 +1,41 @@
+//! Accumulates `syn::Error`s found while walking a derive input so a user
+//! sees every `#[xml(...)]` mistake from one compile instead of stopping at
+//! the first `panic!`.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+
+#[derive(Default)]
+pub(crate) struct Errors {
+    errors: Vec<syn::Error>,
+}
+
+impl Errors {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an error spanned on `tokens` (typically the offending field,
+    /// variant, or attribute).
+    pub(crate) fn push_spanned(&mut self, tokens: impl ToTokens, message: impl std::fmt::Display) {
+        self.errors.push(syn::Error::new_spanned(tokens, message));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Folds every accumulated error into a single token stream of
+    /// `compile_error!` invocations, one per offending span.
+    pub(crate) fn into_compile_error(self) -> TokenStream2 {
+        self.errors
+            .into_iter()
+            .map(|err| err.to_compile_error())
+            .collect()
+    }
+}
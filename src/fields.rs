@@ -0,0 +1,306 @@
+//! Shared `#[xml(...)]` field parsing used by both the `ToTwiML` and
+//! `FromTwiML` derives: which field is an attribute, which one holds text
+//! or nested-element content, and what XML name each maps to.
+
+use crate::case::{self, Case};
+use crate::errors::Errors;
+use syn::Type;
+
+/// A field marked `#[xml(attribute = "...")]`, with its optional
+/// `skip_if`/`with` hooks.
+pub(crate) struct AttrField {
+    pub(crate) ident: syn::Ident,
+    pub(crate) xml_name: String,
+    pub(crate) ty: Type,
+    /// `#[xml(attribute = "...", skip_if = "path::to::fn")]`: the attribute
+    /// is omitted when `fn(&self.field) -> bool` returns `true`.
+    pub(crate) skip_if: Option<syn::Path>,
+    /// `#[xml(attribute = "...", with = "path::to::fn")]`: the attribute
+    /// value is produced by `fn(&self.field) -> String` instead of
+    /// `ToString::to_string`.
+    pub(crate) with: Option<syn::Path>,
+}
+
+/// A content-producing field, in struct-declaration order.
+pub(crate) enum ContentItem {
+    /// `#[xml(content)]` on a plain `String`/`Option<String>` field: written
+    /// as raw `Characters` text directly inside the element.
+    Text { ident: syn::Ident, ty: Type },
+    /// `#[xml(content)]` on a `Vec<T>`/custom-type field: each item writes
+    /// (or is read as) its own nested element via `write_xml`/`parse_body`.
+    /// `tag` is the literal child element name `FromTwiML` expects when
+    /// dispatching an incoming `StartElement` to this field, set only by an
+    /// explicit field-level `#[xml(content, rename = "...")]`. Without one,
+    /// `tag` is `None` and dispatch instead calls the inner type's generated
+    /// `accepts_tag`, since a single literal can't stand in for an enum's
+    /// per-variant tags the way it can for a struct's one element name.
+    Nested { ident: syn::Ident, ty: Type, tag: Option<String> },
+    /// `#[xml(flatten_text = "Tag")]`: the field's value is wrapped in its
+    /// own `<Tag>...</Tag>` child element.
+    FlattenText { ident: syn::Ident, ty: Type, tag: String },
+}
+
+/// The `#[xml(...)]`-driven layout of a struct's fields.
+pub(crate) struct StructFields {
+    pub(crate) attr_fields: Vec<AttrField>,
+    pub(crate) content_items: Vec<ContentItem>,
+}
+
+/// Walks a struct's fields, applying `rename_all` to any attribute whose
+/// name wasn't set explicitly, and records one diagnostic per malformed
+/// `#[xml(...)]` usage instead of aborting on the first one.
+pub(crate) fn collect(data: &syn::DataStruct, rename_all: Option<Case>, errors: &mut Errors) -> StructFields {
+    let mut content_items = Vec::new();
+    let mut attr_fields = Vec::new();
+    let mut text_seen = false;
+
+    for field in data.fields.iter() {
+        let field_name = match field.ident.as_ref() {
+            Some(ident) => ident,
+            None => {
+                errors.push_spanned(field, "ToTwiML/FromTwiML fields must be named");
+                continue;
+            }
+        };
+        let field_type = &field.ty;
+        let mut xml_name = field_name.to_string();
+        let mut xml_name_explicit = false;
+        let mut is_attribute = false;
+        let mut is_content = false;
+        let mut skip_if = None;
+        let mut with = None;
+        let mut flatten_text_tag = None;
+
+        for attr in &field.attrs {
+            if attr.path.is_ident("xml") {
+                if let Ok(meta) = attr.parse_meta() {
+                    match meta {
+                        syn::Meta::List(list) => {
+                            for nested in list.nested {
+                                match nested {
+                                    syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) => {
+                                        if nv.path.is_ident("attribute") {
+                                            if let syn::Lit::Str(lit) = nv.lit {
+                                                xml_name = lit.value();
+                                                xml_name_explicit = true;
+                                                is_attribute = true;
+                                            } else {
+                                                errors.push_spanned(&nv.lit, "`attribute` expects a string literal");
+                                            }
+                                        } else if nv.path.is_ident("rename") {
+                                            if let syn::Lit::Str(lit) = nv.lit {
+                                                xml_name = lit.value();
+                                                xml_name_explicit = true;
+                                            } else {
+                                                errors.push_spanned(&nv.lit, "`rename` expects a string literal");
+                                            }
+                                        } else if nv.path.is_ident("skip_if") {
+                                            if let syn::Lit::Str(lit) = &nv.lit {
+                                                match lit.parse::<syn::Path>() {
+                                                    Ok(path) => skip_if = Some(path),
+                                                    Err(_) => errors.push_spanned(
+                                                        &nv.lit,
+                                                        format!("`skip_if` is not a valid path: `{}`", lit.value()),
+                                                    ),
+                                                }
+                                            } else {
+                                                errors.push_spanned(&nv.lit, "`skip_if` expects a string literal");
+                                            }
+                                        } else if nv.path.is_ident("with") {
+                                            if let syn::Lit::Str(lit) = &nv.lit {
+                                                match lit.parse::<syn::Path>() {
+                                                    Ok(path) => with = Some(path),
+                                                    Err(_) => errors.push_spanned(
+                                                        &nv.lit,
+                                                        format!("`with` is not a valid path: `{}`", lit.value()),
+                                                    ),
+                                                }
+                                            } else {
+                                                errors.push_spanned(&nv.lit, "`with` expects a string literal");
+                                            }
+                                        } else if nv.path.is_ident("flatten_text") {
+                                            if let syn::Lit::Str(lit) = nv.lit {
+                                                flatten_text_tag = Some(lit.value());
+                                            } else {
+                                                errors.push_spanned(&nv.lit, "`flatten_text` expects a string literal");
+                                            }
+                                        } else {
+                                            errors.push_spanned(
+                                                &nv.path,
+                                                format!(
+                                                    "unknown `xml` attribute key `{}`",
+                                                    nv.path.get_ident().map(|i| i.to_string()).unwrap_or_default()
+                                                ),
+                                            );
+                                        }
+                                    }
+                                    syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                                        if path.is_ident("content") {
+                                            is_content = true;
+                                        } else if path.is_ident("attribute") {
+                                            is_attribute = true;
+                                        } else {
+                                            errors.push_spanned(
+                                                &path,
+                                                format!(
+                                                    "unknown `xml` attribute key `{}`",
+                                                    path.get_ident().map(|i| i.to_string()).unwrap_or_default()
+                                                ),
+                                            );
+                                        }
+                                    }
+                                    other => {
+                                        errors.push_spanned(&other, "unsupported `xml` attribute usage");
+                                    }
+                                }
+                            }
+                        }
+                        other => {
+                            errors.push_spanned(&other, "expected `#[xml(...)]`");
+                        }
+                    }
+                }
+            }
+        }
+
+        if is_attribute && is_vec_or_option_vec(field_type) {
+            errors.push_spanned(
+                field,
+                "`#[xml(attribute)]` cannot be used on a `Vec`/`Option<Vec<_>>` field, which has no single string representation",
+            );
+        }
+
+        if is_attribute {
+            if !xml_name_explicit {
+                if let Some(case) = rename_all {
+                    xml_name = case::convert(&xml_name, case);
+                }
+            }
+            attr_fields.push(AttrField {
+                ident: field_name.clone(),
+                xml_name: xml_name.clone(),
+                ty: field_type.clone(),
+                skip_if,
+                with,
+            });
+        }
+
+        if is_content {
+            if is_vec_or_option_vec(field_type) || is_custom_type(field_type) {
+                let tag = xml_name_explicit.then(|| xml_name.clone());
+                content_items.push(ContentItem::Nested {
+                    ident: field_name.clone(),
+                    ty: field_type.clone(),
+                    tag,
+                });
+            } else {
+                if text_seen {
+                    errors.push_spanned(field, "only one plain-text `#[xml(content)]` field is allowed per struct");
+                }
+                text_seen = true;
+                content_items.push(ContentItem::Text {
+                    ident: field_name.clone(),
+                    ty: field_type.clone(),
+                });
+            }
+        }
+
+        if let Some(tag) = flatten_text_tag {
+            content_items.push(ContentItem::FlattenText {
+                ident: field_name.clone(),
+                ty: field_type.clone(),
+                tag,
+            });
+        }
+    }
+
+    StructFields {
+        attr_fields,
+        content_items,
+    }
+}
+
+pub(crate) fn is_optional(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == "Option";
+        }
+    }
+    false
+}
+
+pub(crate) fn is_vec_or_option_vec(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "Vec" {
+                return true;
+            } else if segment.ident == "Option" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(Type::Path(inner_type))) = args.args.first() {
+                        if let Some(inner_segment) = inner_type.path.segments.last() {
+                            return inner_segment.ident == "Vec";
+                        }
+                    }
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Unwraps `Option<Vec<T>>`/`Vec<T>`/`Option<T>` down to the element type
+/// `T`, used by `FromTwiML` to know which concrete type to recurse into for
+/// a nested-content field.
+pub(crate) fn element_type(ty: &Type) -> &Type {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "Vec" || segment.ident == "Option" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                        return element_type(inner);
+                    }
+                }
+            }
+        }
+    }
+    ty
+}
+
+pub(crate) fn is_custom_type(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        let type_name = type_path.path.segments.last().unwrap().ident.to_string();
+        !matches!(type_name.as_str(), "String" | "i32" | "bool" | "Option" | "Vec")
+    } else {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Regression test for chunk0-3: two independently malformed
+    /// `#[xml(...)]` usages in one struct must both surface as errors from a
+    /// single `collect()` call, instead of `collect()` panicking or stopping
+    /// after the first one.
+    #[test]
+    fn collect_accumulates_one_error_per_malformed_attribute_instead_of_stopping_at_the_first() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            struct Bad {
+                #[xml(rename = 1)]
+                first: String,
+                #[xml(attribute = 2)]
+                second: String,
+            }
+        };
+        let data = match input.data {
+            syn::Data::Struct(data) => data,
+            _ => panic!("expected a struct"),
+        };
+
+        let mut errors = Errors::new();
+        collect(&data, None, &mut errors);
+
+        assert_eq!(errors.len(), 2);
+    }
+}
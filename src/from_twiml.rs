@@ -0,0 +1,336 @@
+//! `FromTwiML` derive: parses TwiML XML back into structs/enums from an
+//! `xml::reader::EventReader`, driven by the same `#[xml(attribute = "...")]`
+//! and `#[xml(content)]` metadata `ToTwiML` writes.
+//!
+//! Each generated type gets an inherent `parse_body(name, attributes,
+//! reader)` that assumes its opening tag has already been read (so a parent
+//! can hand a nested field's `StartElement` straight to the child's type
+//! without an extra lookahead), plus the public `FromTwiML::read_xml` entry
+//! point that scans for that opening tag itself.
+
+use crate::case::Case;
+use crate::errors::Errors;
+use crate::fields::{self, StructFields};
+use crate::variant_xml_name;
+use quote::quote;
+
+pub(crate) fn derive_struct(
+    name: &syn::Ident,
+    data: &syn::DataStruct,
+    element_name: String,
+    rename_all: Option<Case>,
+    errors: &mut Errors,
+) -> proc_macro2::TokenStream {
+    let StructFields { attr_fields, content_items } = fields::collect(data, rename_all, errors);
+
+    let attr_parsers = attr_fields.iter().map(|attr_field| {
+        let fields::AttrField { ident, xml_name, ty, .. } = attr_field;
+        if fields::is_optional(ty) {
+            quote! {
+                let mut #ident = None;
+                for __xml_attr in attributes {
+                    if __xml_attr.name.local_name == #xml_name {
+                        #ident = Some(__xml_attr.value.parse().map_err(|_| {
+                            TwilioError::Parse(format!("failed to parse attribute `{}`", #xml_name))
+                        })?);
+                    }
+                }
+            }
+        } else {
+            quote! {
+                let mut #ident = None;
+                for __xml_attr in attributes {
+                    if __xml_attr.name.local_name == #xml_name {
+                        #ident = Some(__xml_attr.value.parse().map_err(|_| {
+                            TwilioError::Parse(format!("failed to parse attribute `{}`", #xml_name))
+                        })?);
+                    }
+                }
+                let #ident = #ident.ok_or_else(|| {
+                    TwilioError::Parse(format!("missing required attribute `{}`", #xml_name))
+                })?;
+            }
+        }
+    });
+
+    // Per-content-item local-variable init, in struct-declaration order.
+    let content_inits = content_items.iter().map(|item| match item {
+        fields::ContentItem::Text { ident, .. } => quote! { let mut #ident = String::new(); },
+        fields::ContentItem::FlattenText { ident, ty, .. } => {
+            if fields::is_optional(ty) {
+                quote! { let mut #ident = None; }
+            } else {
+                quote! { let mut #ident = String::new(); }
+            }
+        }
+        fields::ContentItem::Nested { ident, ty, .. } => {
+            if fields::is_vec_or_option_vec(ty) {
+                quote! { let mut #ident = Vec::new(); }
+            } else {
+                quote! { let mut #ident = None; }
+            }
+        }
+    });
+
+    // Per-content-item dispatch: one match arm per item, handling plain text
+    // accumulation, a nested element's `parse_body`, or a `flatten_text`
+    // field's own wrapping element.
+    let text_items: Vec<_> = content_items
+        .iter()
+        .filter_map(|item| match item {
+            fields::ContentItem::Text { ident, .. } => Some(ident),
+            _ => None,
+        })
+        .collect();
+    let text_accumulate = (!text_items.is_empty()).then(|| {
+        quote! {
+            ::xml::reader::XmlEvent::Characters(__xml_text) | ::xml::reader::XmlEvent::CData(__xml_text) => {
+                #(#text_items.push_str(&__xml_text);)*
+            }
+        }
+    });
+
+    // Each nested field is only entitled to children whose tag matches its
+    // own expected tag, so multiple `Vec<T>`/custom-type content fields on
+    // one struct don't all collapse into the first field's arm. An explicit
+    // `rename` gives a literal tag to compare against; otherwise the inner
+    // type's own generated `accepts_tag` decides, since an enum's children
+    // are written under their variants' tags rather than one tag named
+    // after the enum.
+    let nested_dispatch = content_items.iter().filter_map(|item| match item {
+        fields::ContentItem::Nested { ident, ty, tag } => {
+            let inner_ty = fields::element_type(ty);
+            let guard = match tag {
+                Some(tag) => quote! { child_name.local_name == #tag },
+                None => quote! { <#inner_ty>::accepts_tag(&child_name.local_name) },
+            };
+            if fields::is_vec_or_option_vec(ty) {
+                Some(quote! {
+                    ::xml::reader::XmlEvent::StartElement { name: child_name, attributes: child_attrs, .. }
+                        if #guard =>
+                    {
+                        #ident.push(<#inner_ty>::parse_body(&child_name.local_name, &child_attrs, reader)?);
+                    }
+                })
+            } else {
+                Some(quote! {
+                    ::xml::reader::XmlEvent::StartElement { name: child_name, attributes: child_attrs, .. }
+                        if #guard =>
+                    {
+                        #ident = Some(<#inner_ty>::parse_body(&child_name.local_name, &child_attrs, reader)?);
+                    }
+                })
+            }
+        }
+        _ => None,
+    });
+
+    // `flatten_text` fields are dispatched by their known wrapper tag, each
+    // reading its own text body up to the matching end element.
+    let flatten_text_dispatch = content_items.iter().filter_map(|item| match item {
+        fields::ContentItem::FlattenText { ident, ty, tag } => {
+            let assign = if fields::is_optional(ty) {
+                quote! { #ident = Some(flatten_text_value); }
+            } else {
+                quote! { #ident = flatten_text_value; }
+            };
+            Some(quote! {
+                ::xml::reader::XmlEvent::StartElement { name: child_name, .. } if child_name.local_name == #tag => {
+                    let mut flatten_text_value = String::new();
+                    loop {
+                        match reader.next()? {
+                            ::xml::reader::XmlEvent::EndElement { name: end_name } if end_name.local_name == #tag => {
+                                break;
+                            }
+                            ::xml::reader::XmlEvent::Characters(text) | ::xml::reader::XmlEvent::CData(text) => {
+                                flatten_text_value.push_str(&text);
+                            }
+                            _ => {}
+                        }
+                    }
+                    #assign
+                }
+            })
+        }
+        _ => None,
+    });
+
+    let field_inits = data.fields.iter().filter_map(|f| f.ident.as_ref()).map(|field_ident| {
+        let is_attr = attr_fields.iter().any(|attr_field| &attr_field.ident == field_ident);
+        let content_item = content_items.iter().find(|item| match item {
+            fields::ContentItem::Text { ident, .. }
+            | fields::ContentItem::Nested { ident, .. }
+            | fields::ContentItem::FlattenText { ident, .. } => ident == field_ident,
+        });
+
+        // Text and nested locals are a bare `String`/`Vec<T>`/`Option<T>`;
+        // convert them to an `Option<_>` or required custom-type field.
+        match content_item {
+            Some(fields::ContentItem::Text { ty, .. }) | Some(fields::ContentItem::Nested { ty, .. }) if fields::is_optional(ty) => {
+                quote! { #field_ident: (!#field_ident.is_empty()).then(|| #field_ident) }
+            }
+            Some(fields::ContentItem::Nested { ty, .. }) if !fields::is_vec_or_option_vec(ty) => quote! {
+                #field_ident: #field_ident.ok_or_else(|| {
+                    TwilioError::Parse(format!("missing child element for `{}`", stringify!(#field_ident)))
+                })?
+            },
+            _ if is_attr || content_item.is_some() => quote! { #field_ident },
+            _ => quote! { #field_ident: ::std::default::Default::default() },
+        }
+    });
+
+    quote! {
+        impl #name {
+            // Whether an incoming child element's tag belongs to this type,
+            // used by a parent's nested-content dispatch to route a
+            // `StartElement` to the right field without guessing from the
+            // Rust type name.
+            pub fn accepts_tag(tag: &str) -> bool {
+                tag == #element_name
+            }
+
+            // Parses the element's body (attributes + children) assuming its
+            // opening tag was already consumed by the caller.
+            pub fn parse_body<R: ::std::io::Read>(
+                _name: &str,
+                attributes: &[::xml::attribute::OwnedAttribute],
+                reader: &mut ::xml::reader::EventReader<R>,
+            ) -> Result<Self, TwilioError> {
+                #(#attr_parsers)*
+                #(#content_inits)*
+
+                loop {
+                    match reader.next()? {
+                        ::xml::reader::XmlEvent::EndElement { name: end_name } if end_name.local_name == #element_name => {
+                            break;
+                        }
+                        #(#flatten_text_dispatch)*
+                        #(#nested_dispatch)*
+                        #text_accumulate
+                        _ => {}
+                    }
+                }
+
+                Ok(Self {
+                    #(#field_inits),*
+                })
+            }
+        }
+
+        impl FromTwiML for #name {
+            fn read_xml<R: ::std::io::Read>(reader: &mut ::xml::reader::EventReader<R>) -> Result<Self, TwilioError> {
+                loop {
+                    match reader.next()? {
+                        ::xml::reader::XmlEvent::StartElement { name, attributes, .. } => {
+                            if name.local_name != #element_name {
+                                return Err(TwilioError::Parse(format!(
+                                    "expected <{}> but found <{}>",
+                                    #element_name, name.local_name
+                                )));
+                            }
+                            return Self::parse_body(&name.local_name, &attributes, reader);
+                        }
+                        ::xml::reader::XmlEvent::EndDocument => {
+                            return Err(TwilioError::Parse(format!(
+                                "expected <{}> but reached end of document",
+                                #element_name
+                            )));
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn derive_enum(
+    name: &syn::Ident,
+    data: &syn::DataEnum,
+    rename_all: Option<Case>,
+    errors: &mut Errors,
+) -> proc_macro2::TokenStream {
+    let mut arms = Vec::new();
+    let mut tag_checks = Vec::new();
+
+    for variant in data.variants.iter() {
+        let variant_ident = &variant.ident;
+        let xml_name = variant_xml_name(variant, rename_all, errors);
+
+        match &variant.fields {
+            // `ToTwiML` writes a newtype variant through the inner value's own
+            // `write_xml`, so it is recognised by the inner type's tag.
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let inner_ty = &fields.unnamed.first().unwrap().ty;
+                tag_checks.push(quote! { <#inner_ty>::accepts_tag(tag) });
+                arms.push(quote! {
+                    __xml_tag if <#inner_ty>::accepts_tag(__xml_tag) => Ok(#name::#variant_ident(<#inner_ty>::parse_body(name, attributes, reader)?)),
+                });
+            }
+            syn::Fields::Unit => {
+                tag_checks.push(quote! { tag == #xml_name });
+                arms.push(quote! {
+                    #xml_name => {
+                        loop {
+                            match reader.next()? {
+                                ::xml::reader::XmlEvent::EndElement { name: end_name } if end_name.local_name == #xml_name => {
+                                    break;
+                                }
+                                _ => {}
+                            }
+                        }
+                        Ok(#name::#variant_ident)
+                    }
+                });
+            }
+            _ => {
+                errors.push_spanned(
+                    variant,
+                    "FromTwiML enum variants must be unit variants or a single-field tuple variant",
+                );
+            }
+        }
+    }
+
+    quote! {
+        impl #name {
+            // Whether an incoming child element's tag belongs to any variant
+            // of this enum. Unlike a struct, an enum has no single element
+            // name of its own — a unit variant writes its own tag and a
+            // newtype variant its inner type's tag — so a parent's
+            // nested-content dispatch must check all of them.
+            pub fn accepts_tag(tag: &str) -> bool {
+                false #(|| #tag_checks)*
+            }
+
+            pub fn parse_body<R: ::std::io::Read>(
+                name: &str,
+                attributes: &[::xml::attribute::OwnedAttribute],
+                reader: &mut ::xml::reader::EventReader<R>,
+            ) -> Result<Self, TwilioError> {
+                match name {
+                    #(#arms)*
+                    other => Err(TwilioError::Parse(format!("unrecognized child element <{}>", other))),
+                }
+            }
+        }
+
+        impl FromTwiML for #name {
+            fn read_xml<R: ::std::io::Read>(reader: &mut ::xml::reader::EventReader<R>) -> Result<Self, TwilioError> {
+                loop {
+                    match reader.next()? {
+                        ::xml::reader::XmlEvent::StartElement { name, attributes, .. } => {
+                            return Self::parse_body(&name.local_name, &attributes, reader);
+                        }
+                        ::xml::reader::XmlEvent::EndDocument => {
+                            return Err(TwilioError::Parse(
+                                "reached end of document looking for an element".to_string(),
+                            ));
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+        }
+    }
+}
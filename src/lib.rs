@@ -1,131 +1,170 @@
 extern crate proc_macro;
 
+mod case;
+mod errors;
+mod fields;
+mod from_twiml;
 
+use case::Case;
+use errors::Errors;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{self, DeriveInput, Type};
+use syn::{self, DeriveInput};
 
 #[proc_macro_derive(ToTwiML, attributes(xml))]
 pub fn to_twiml_derive(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
+    let mut errors = Errors::new();
+    let (container_rename, rename_all) = container_renaming(&input, &mut errors);
 
-    let data = match input.data {
-        syn::Data::Struct(ref data) => data,
-        _ => panic!("ToTwiML can only be derived for structs"),
+    let expanded = match &input.data {
+        syn::Data::Struct(data) => {
+            let element_name = container_rename.unwrap_or_else(|| name.to_string());
+            derive_struct(name, data, element_name, rename_all, &mut errors)
+        }
+        syn::Data::Enum(data) => derive_enum(name, data, rename_all, &mut errors),
+        syn::Data::Union(_) => {
+            errors.push_spanned(&input, "ToTwiML can only be derived for structs and enums");
+            quote! {}
+        }
     };
 
-    let mut text_field = None;       // For text content (e.g., String)
-    let mut nested_field = None;     // For nested elements (e.g., Vec<T> or custom types)
-    let mut attr_fields = Vec::new(); // For attributes
-
-    // Process each field
-    for field in data.fields.iter() {
-        let field_name = field.ident.as_ref().expect("Fields must be named");
-        let field_type = &field.ty;
-        let mut xml_name = field_name.to_string();
-        let mut is_attribute = false;
-        let mut is_content = false;
-
-        for attr in &field.attrs {
-            if attr.path.is_ident("xml") {
-                if let Ok(meta) = attr.parse_meta() {
-                    match meta {
-                        syn::Meta::List(list) => {
-                            for nested in list.nested {
-                                match nested {
-                                    syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) => {
-                                        if nv.path.is_ident("attribute") {
-                                            if let syn::Lit::Str(lit) = nv.lit {
-                                                xml_name = lit.value();
-                                                is_attribute = true;
-                                            }
-                                        }
-                                    }
-                                    syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
-                                        if path.is_ident("content") {
-                                            is_content = true;
-                                            if is_vec_or_option_vec(field_type) || is_custom_type(field_type) {
-                                                nested_field = Some((field_name.clone(), field_type.clone()));
-                                            } else {
-                                                text_field = Some((field_name.clone(), field_type.clone()));
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
+    if !errors.is_empty() {
+        return TokenStream::from(errors.into_compile_error());
+    }
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_derive(FromTwiML, attributes(xml))]
+pub fn from_twiml_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let mut errors = Errors::new();
+    let (container_rename, rename_all) = container_renaming(&input, &mut errors);
 
-        if is_attribute {
-            attr_fields.push((field_name.clone(), xml_name, field_type.clone()));
+    let expanded = match &input.data {
+        syn::Data::Struct(data) => {
+            let element_name = container_rename.unwrap_or_else(|| name.to_string());
+            from_twiml::derive_struct(name, data, element_name, rename_all, &mut errors)
+        }
+        syn::Data::Enum(data) => from_twiml::derive_enum(name, data, rename_all, &mut errors),
+        syn::Data::Union(_) => {
+            errors.push_spanned(&input, "FromTwiML can only be derived for structs and enums");
+            quote! {}
         }
+    };
+
+    if !errors.is_empty() {
+        return TokenStream::from(errors.into_compile_error());
     }
 
+    TokenStream::from(expanded)
+}
+
+// Generates `impl ToTwiML` for a struct: attributes from `#[xml(attribute)]`
+// fields, content (text/nested elements/`flatten_text`) from the
+// `#[xml(content)]`/`#[xml(flatten_text = "...")]` fields, in declaration
+// order.
+fn derive_struct(
+    name: &syn::Ident,
+    data: &syn::DataStruct,
+    element_name: String,
+    rename_all: Option<Case>,
+    errors: &mut Errors,
+) -> proc_macro2::TokenStream {
+    let fields::StructFields { attr_fields, content_items } = fields::collect(data, rename_all, errors);
+
     // Generate attribute collectors
-    let attr_collectors = attr_fields.iter().map(|(field, xml_name, field_type)| {
-        if is_optional(field_type) {
+    let attr_collectors = attr_fields.iter().map(|attr_field| {
+        let fields::AttrField { ident, xml_name, ty, skip_if, with } = attr_field;
+
+        let to_string = match with {
+            Some(with_fn) => quote! { #with_fn(value) },
+            None => quote! { value.to_string() },
+        };
+        let skip_check = match skip_if {
+            Some(skip_fn) => quote! { #skip_fn(value) },
+            None => quote! { false },
+        };
+
+        if fields::is_optional(ty) {
             quote! {
-                if let Some(value) = &self.#field {
-                    attributes.push((#xml_name.to_string(), value.to_string()));
+                if let Some(value) = &self.#ident {
+                    if !(#skip_check) {
+                        attributes.push((#xml_name.to_string(), #to_string));
+                    }
                 }
             }
         } else {
             quote! {
-                attributes.push((#xml_name.to_string(), self.#field.to_string()));
+                let value = &self.#ident;
+                if !(#skip_check) {
+                    attributes.push((#xml_name.to_string(), #to_string));
+                }
             }
         }
     });
 
-    // Generate text content write logic
-    let text_write = if let Some((text_field, field_type)) = text_field {
-        if is_optional(&field_type) {
-            quote! {
-                if let Some(value) = &self.#text_field {
-                    writer.write(::xml::writer::XmlEvent::Characters(value))?;
+    // Generate content writes (text, nested elements, flatten_text) in
+    // struct-declaration order.
+    let content_writes = content_items.iter().map(|item| match item {
+        fields::ContentItem::Text { ident, ty } => {
+            if fields::is_optional(ty) {
+                quote! {
+                    if let Some(value) = &self.#ident {
+                        writer.write(::xml::writer::XmlEvent::Characters(value))?;
+                    }
+                }
+            } else {
+                quote! {
+                    writer.write(::xml::writer::XmlEvent::Characters(&self.#ident))?;
                 }
-            }
-        } else {
-            quote! {
-                writer.write(::xml::writer::XmlEvent::Characters(&self.#text_field))?;
             }
         }
-    } else {
-        quote! {}
-    };
-
-    // Generate nested elements write logic
-    let nested_write = if let Some((nested_field, field_type)) = nested_field {
-        if is_vec_or_option_vec(&field_type) {
-            if is_optional(&field_type) {
-                quote! {
-                    if let Some(items) = &self.#nested_field {
-                        for item in items {
+        fields::ContentItem::Nested { ident, ty, .. } => {
+            if fields::is_vec_or_option_vec(ty) {
+                if fields::is_optional(ty) {
+                    quote! {
+                        if let Some(items) = &self.#ident {
+                            for item in items {
+                                item.write_xml(writer)?;
+                            }
+                        }
+                    }
+                } else {
+                    quote! {
+                        for item in &self.#ident {
                             item.write_xml(writer)?;
                         }
                     }
                 }
             } else {
+                // Custom type like Noun
                 quote! {
-                    for item in &self.#nested_field {
-                        item.write_xml(writer)?;
-                    }
+                    self.#ident.write_xml(writer)?;
                 }
             }
-        } else {
-            // Custom type like Noun
-            quote! {
-                self.#nested_field.write_xml(writer)?;
+        }
+        fields::ContentItem::FlattenText { ident, ty, tag } => {
+            if fields::is_optional(ty) {
+                quote! {
+                    if let Some(value) = &self.#ident {
+                        writer.write(::xml::writer::XmlEvent::start_element(#tag))?;
+                        writer.write(::xml::writer::XmlEvent::Characters(value))?;
+                        writer.write(::xml::writer::XmlEvent::end_element())?;
+                    }
+                }
+            } else {
+                quote! {
+                    writer.write(::xml::writer::XmlEvent::start_element(#tag))?;
+                    writer.write(::xml::writer::XmlEvent::Characters(&self.#ident))?;
+                    writer.write(::xml::writer::XmlEvent::end_element())?;
+                }
             }
         }
-    } else {
-        quote! {}
-    };
+    });
 
     // Generate the full implementation
     let expanded = quote! {
@@ -135,56 +174,144 @@ pub fn to_twiml_derive(input: TokenStream) -> TokenStream {
                 let mut attributes = Vec::new();
                 #(#attr_collectors)*
 
-                let mut element = XmlEvent::start_element(stringify!(#name));
+                let mut element = XmlEvent::start_element(#element_name);
                 for (key, value) in &attributes {
                     element = element.attr(key.as_str(), value.as_str());
                 }
                 writer.write(element)?;
-                #text_write
-                #nested_write
+                #(#content_writes)*
                 writer.write(XmlEvent::end_element())?;
                 Ok(())
             }
         }
     };
 
-    TokenStream::from(expanded)
+    expanded
 }
 
-// Helper functions
-fn is_optional(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            return segment.ident == "Option";
+// Generates `impl ToTwiML` for an enum: each variant becomes a match arm.
+// Newtype variants (`Say(Say)`) forward to the inner value's `write_xml`;
+// unit variants write an empty self-closing element named after the variant.
+fn derive_enum(
+    name: &syn::Ident,
+    data: &syn::DataEnum,
+    rename_all: Option<Case>,
+    errors: &mut Errors,
+) -> proc_macro2::TokenStream {
+    let mut match_arms = Vec::new();
+
+    for variant in data.variants.iter() {
+        let variant_ident = &variant.ident;
+        let xml_name = variant_xml_name(variant, rename_all, errors);
+
+        match &variant.fields {
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                match_arms.push(quote! {
+                    #name::#variant_ident(inner) => inner.write_xml(writer),
+                });
+            }
+            syn::Fields::Unit => {
+                match_arms.push(quote! {
+                    #name::#variant_ident => {
+                        writer.write(::xml::writer::XmlEvent::start_element(#xml_name))?;
+                        writer.write(::xml::writer::XmlEvent::end_element())?;
+                        Ok(())
+                    }
+                });
+            }
+            _ => {
+                errors.push_spanned(
+                    variant,
+                    "ToTwiML enum variants must be unit variants or a single-field tuple variant",
+                );
+            }
+        }
+    }
+
+    quote! {
+        impl ToTwiML for #name {
+            fn write_xml(&self, writer: &mut ::xml::writer::EventWriter<Vec<u8>>) -> Result<(), TwilioError> {
+                use ::xml::writer::EventWriter;
+                match self {
+                    #(#match_arms)*
+                }
+            }
         }
     }
-    false
 }
 
-fn is_vec_or_option_vec(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            if segment.ident == "Vec" {
-                return true;
-            } else if segment.ident == "Option" {
-                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                    if let Some(syn::GenericArgument::Type(Type::Path(inner_type))) = args.args.first() {
-                        if let Some(inner_segment) = inner_type.path.segments.last() {
-                            return inner_segment.ident == "Vec";
+// Resolves the XML tag for an enum variant: an explicit `#[xml(rename =
+// "...")]` wins, otherwise the variant name is cased via `rename_all`.
+pub(crate) fn variant_xml_name(variant: &syn::Variant, rename_all: Option<Case>, errors: &mut Errors) -> String {
+    for attr in &variant.attrs {
+        if attr.path.is_ident("xml") {
+            if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+                for nested in list.nested {
+                    match nested {
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("rename") => {
+                            if let syn::Lit::Str(lit) = nv.lit {
+                                return lit.value();
+                            } else {
+                                errors.push_spanned(&nv.lit, "`rename` expects a string literal");
+                            }
+                        }
+                        other => {
+                            errors.push_spanned(&other, "unknown `xml` attribute usage on enum variant");
                         }
                     }
                 }
             }
         }
     }
-    false
+
+    let default_name = variant.ident.to_string();
+    match rename_all {
+        Some(case) => case::convert(&default_name, case),
+        None => default_name,
+    }
 }
 
-fn is_custom_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        let type_name = type_path.path.segments.last().unwrap().ident.to_string();
-        !matches!(type_name.as_str(), "String" | "i32" | "bool" | "Option" | "Vec")
-    } else {
-        true
+// Helper functions
+
+/// Reads the container-level `#[xml(rename = "...")]` and
+/// `#[xml(rename_all = "...")]` attributes off a struct/enum.
+pub(crate) fn container_renaming(input: &DeriveInput, errors: &mut Errors) -> (Option<String>, Option<Case>) {
+    let mut rename = None;
+    let mut rename_all = None;
+
+    for attr in &input.attrs {
+        if attr.path.is_ident("xml") {
+            if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+                for nested in list.nested {
+                    match nested {
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("rename") => {
+                            if let syn::Lit::Str(lit) = nv.lit {
+                                rename = Some(lit.value());
+                            } else {
+                                errors.push_spanned(&nv.lit, "`rename` expects a string literal");
+                            }
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("rename_all") => {
+                            if let syn::Lit::Str(lit) = &nv.lit {
+                                match Case::from_str(&lit.value()) {
+                                    Some(case) => rename_all = Some(case),
+                                    None => errors.push_spanned(
+                                        &nv.lit,
+                                        format!("unsupported rename_all case: `{}`", lit.value()),
+                                    ),
+                                }
+                            } else {
+                                errors.push_spanned(&nv.lit, "`rename_all` expects a string literal");
+                            }
+                        }
+                        other => {
+                            errors.push_spanned(&other, "unknown `xml` container attribute usage");
+                        }
+                    }
+                }
+            }
+        }
     }
+
+    (rename, rename_all)
 }
\ No newline at end of file
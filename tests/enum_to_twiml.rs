@@ -0,0 +1,55 @@
+//! Regression test for chunk0-2: `ToTwiML` derived on an enum routes unit
+//! variants to their own self-closing element and single-field tuple
+//! variants to the inner value's own `write_xml`.
+//!
+//! The `ToTwiML`/`TwilioError` types the derive output assumes are in scope
+//! aren't part of this crate (they live in the companion runtime crate), so
+//! this test provides minimal stand-ins.
+
+use twiml_derive::ToTwiML;
+
+pub trait ToTwiML {
+    fn write_xml(&self, writer: &mut ::xml::writer::EventWriter<Vec<u8>>) -> Result<(), TwilioError>;
+}
+
+#[derive(Debug)]
+pub enum TwilioError {
+    Io(String),
+}
+
+impl From<::xml::writer::Error> for TwilioError {
+    fn from(err: ::xml::writer::Error) -> Self {
+        TwilioError::Io(err.to_string())
+    }
+}
+
+#[derive(ToTwiML)]
+struct Pause {
+    #[xml(attribute = "length")]
+    length: i32,
+}
+
+#[derive(ToTwiML)]
+enum Verb {
+    Hangup,
+    Pause(Pause),
+}
+
+fn write(verb: &Verb) -> String {
+    let mut writer = ::xml::writer::EventWriter::new(Vec::new());
+    verb.write_xml(&mut writer).unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn unit_variant_writes_its_own_empty_element() {
+    let xml = write(&Verb::Hangup);
+    assert!(xml.contains("<Hangup"), "expected a <Hangup> element, got: {xml}");
+}
+
+#[test]
+fn tuple_variant_forwards_to_the_inner_values_write_xml() {
+    let xml = write(&Verb::Pause(Pause { length: 5 }));
+    assert!(xml.contains("<Pause"), "expected a <Pause> element, got: {xml}");
+    assert!(xml.contains("length=\"5\""), "expected the inner type's attribute, got: {xml}");
+}
@@ -0,0 +1,92 @@
+//! Regression test for chunk0-5: `#[xml(attribute = "...", skip_if = "...")]`
+//! omits the attribute instead of writing it, and `#[xml(attribute = "...",
+//! with = "...")]` serializes the attribute through a custom function
+//! instead of `ToString::to_string`.
+//!
+//! The `ToTwiML`/`FromTwiML`/`TwilioError` types the derive output assumes
+//! are in scope aren't part of this crate (they live in the companion
+//! runtime crate), so this test provides minimal stand-ins.
+
+use twiml_derive::{FromTwiML, ToTwiML};
+
+pub trait ToTwiML {
+    fn write_xml(&self, writer: &mut ::xml::writer::EventWriter<Vec<u8>>) -> Result<(), TwilioError>;
+}
+
+pub trait FromTwiML: Sized {
+    fn read_xml<R: std::io::Read>(reader: &mut ::xml::reader::EventReader<R>) -> Result<Self, TwilioError>;
+}
+
+#[derive(Debug)]
+pub enum TwilioError {
+    Io(String),
+    Parse(String),
+}
+
+impl From<::xml::writer::Error> for TwilioError {
+    fn from(err: ::xml::writer::Error) -> Self {
+        TwilioError::Io(err.to_string())
+    }
+}
+
+impl From<::xml::reader::Error> for TwilioError {
+    fn from(err: ::xml::reader::Error) -> Self {
+        TwilioError::Io(err.to_string())
+    }
+}
+
+fn pad_three_digits(value: &i32) -> String {
+    format!("{:03}", value)
+}
+
+fn is_default_voice(value: &String) -> bool {
+    value == "man"
+}
+
+#[derive(ToTwiML, FromTwiML, Debug, PartialEq, Default)]
+struct Say {
+    #[xml(attribute = "voice", skip_if = "is_default_voice")]
+    voice: String,
+    #[xml(attribute = "loop", with = "pad_three_digits")]
+    loop_count: i32,
+    #[xml(content)]
+    text: String,
+}
+
+fn write(say: &Say) -> String {
+    let mut writer = ::xml::writer::EventWriter::new(Vec::new());
+    say.write_xml(&mut writer).unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn with_hook_serializes_the_attribute_and_still_round_trips() {
+    let say = Say { voice: "alice".to_string(), loop_count: 7, text: "hello".to_string() };
+
+    let xml = write(&say);
+    assert!(xml.contains("loop=\"007\""), "expected the padded attribute, got: {xml}");
+
+    let mut reader = ::xml::reader::EventReader::new(xml.as_bytes());
+    let parsed = Say::read_xml(&mut reader).unwrap();
+    assert_eq!(parsed, say);
+}
+
+#[test]
+fn skip_if_hook_omits_the_attribute_when_true() {
+    let say = Say { voice: "man".to_string(), loop_count: 1, text: "hi".to_string() };
+
+    let xml = write(&say);
+    assert!(!xml.contains("voice="), "expected the default voice to be omitted, got: {xml}");
+}
+
+#[test]
+fn skip_if_hook_keeps_the_attribute_and_round_trips_when_false() {
+    let say = Say { voice: "alice".to_string(), loop_count: 1, text: "hi".to_string() };
+
+    let xml = write(&say);
+    assert!(xml.contains("voice=\"alice\""), "expected the attribute to be written, got: {xml}");
+
+    let mut reader = ::xml::reader::EventReader::new(xml.as_bytes());
+    let parsed = Say::read_xml(&mut reader).unwrap();
+    assert_eq!(parsed, say);
+}